@@ -1,8 +1,11 @@
 use crate::domain::{
-    CompositionRequest, ForegroundKind, OverlayPosition, QualityProfile, SlideTiming,
-    ValidationError,
+    CompositionRequest, Encoder, ForegroundKind, OutputFormat, OverlayPosition, QualityProfile,
+    SlideTiming, ValidationError,
 };
 
+pub mod encode_parallel;
+pub mod probe;
+
 pub fn validate_timings(timings: &[SlideTiming]) -> Result<(), ValidationError> {
     if timings.is_empty() {
         return Err(ValidationError::EmptyTimings);
@@ -26,14 +29,161 @@ pub fn validate_request(req: &CompositionRequest) -> Result<(), ValidationError>
             req.overlay_relative_width,
         ));
     }
+    if req.output_format == OutputFormat::HlsFmp4 && !is_directory_style(&req.output_path) {
+        return Err(ValidationError::HlsRequiresDirectory(
+            req.output_path.clone(),
+        ));
+    }
+    validate_fast_regions(&req.fast_regions)?;
     validate_timings(&req.timings)
 }
 
+/// Validate speed-ramp regions: each must have `start < end` and a positive
+/// multiplier, and they must be sorted by start and non-overlapping.
+pub fn validate_fast_regions(regions: &[(f64, f64, f64)]) -> Result<(), ValidationError> {
+    let mut prev_end = f64::NEG_INFINITY;
+    for &(start, end, mult) in regions {
+        if !(start < end) || !(mult > 0.0) || start < prev_end {
+            return Err(ValidationError::InvalidFastRegions);
+        }
+        prev_end = end;
+    }
+    Ok(())
+}
+
+/// Decompose a speed multiplier into `atempo` stages, each within ffmpeg's
+/// `[0.5, 2.0]` per-filter range.
+pub fn atempo_chain(speed: f64) -> Vec<f64> {
+    let mut stages = Vec::new();
+    let mut s = speed;
+    while s > 2.0 {
+        stages.push(2.0);
+        s /= 2.0;
+    }
+    while s < 0.5 {
+        stages.push(0.5);
+        s /= 0.5;
+    }
+    stages.push(s);
+    stages
+}
+
+struct TimelinePiece {
+    start: f64,
+    end: Option<f64>,
+    speed: f64,
+}
+
+/// Split the timeline into alternating normal/accelerated pieces; the final
+/// piece is open-ended so it runs to the end of the input.
+fn fast_region_pieces(regions: &[(f64, f64, f64)]) -> Vec<TimelinePiece> {
+    let mut pieces = Vec::new();
+    let mut cursor = 0.0;
+    for &(start, end, mult) in regions {
+        if start > cursor {
+            pieces.push(TimelinePiece {
+                start: cursor,
+                end: Some(start),
+                speed: 1.0,
+            });
+        }
+        pieces.push(TimelinePiece {
+            start,
+            end: Some(end),
+            speed: mult,
+        });
+        cursor = end;
+    }
+    pieces.push(TimelinePiece {
+        start: cursor,
+        end: None,
+        speed: 1.0,
+    });
+    pieces
+}
+
+/// Build one split→trim→time-scale→concat chain for a single stream, labelled
+/// `[{out}]`. `audio` selects the audio filters (`asplit`/`atrim`/`asetpts`
+/// plus an `atempo` chain) over the video ones (`split`/`trim`/`setpts`).
+fn fast_region_chain(input: &str, out: &str, pieces: &[TimelinePiece], audio: bool) -> String {
+    let k = pieces.len();
+    let (split, trim, setpts) = if audio {
+        ("asplit", "atrim", "asetpts")
+    } else {
+        ("split", "trim", "setpts")
+    };
+
+    let mut chain = format!("[{input}]{split}={k}");
+    for i in 0..k {
+        chain.push_str(&format!("[{out}s{i}]"));
+    }
+    chain.push(';');
+
+    let mut concat = String::new();
+    for (i, p) in pieces.iter().enumerate() {
+        let range = match p.end {
+            Some(e) => format!("start={}:end={}", p.start, e),
+            None => format!("start={}", p.start),
+        };
+        chain.push_str(&format!("[{out}s{i}]{trim}={range},"));
+        if audio {
+            chain.push_str(&format!("{setpts}=PTS-STARTPTS"));
+            if p.speed != 1.0 {
+                for f in atempo_chain(p.speed) {
+                    chain.push_str(&format!(",atempo={f}"));
+                }
+            }
+        } else if p.speed != 1.0 {
+            chain.push_str(&format!("{setpts}=(PTS-STARTPTS)/{}", p.speed));
+        } else {
+            chain.push_str(&format!("{setpts}=PTS-STARTPTS"));
+        }
+        chain.push_str(&format!("[{out}p{i}];"));
+        concat.push_str(&format!("[{out}p{i}]"));
+    }
+
+    let (v, a) = if audio { (0, 1) } else { (1, 0) };
+    chain.push_str(&format!("{concat}concat=n={k}:v={v}:a={a}[{out}]"));
+    chain
+}
+
+/// Build the `filter_complex` prefix that time-scales the speed-ramp regions.
+/// Both video layers are trimmed into the *same* pieces and scaled identically
+/// so the overlay stays aligned with the sped-up background (`[bgff]`/`[fgff]`),
+/// and, when the source carries audio, the track is `atempo`-matched (`[aff]`).
+fn build_fast_region_filter(
+    bg: &str,
+    fg: &str,
+    regions: &[(f64, f64, f64)],
+    has_audio: bool,
+) -> String {
+    let pieces = fast_region_pieces(regions);
+    let mut parts = vec![
+        fast_region_chain(bg, "bgff", &pieces, false),
+        fast_region_chain(fg, "fgff", &pieces, false),
+    ];
+    if has_audio {
+        parts.push(fast_region_chain("0:a", "aff", &pieces, true));
+    }
+    parts.join(";")
+}
+
+/// Whether a path looks like a directory (no file extension) rather than a
+/// progressive file. HLS writes a playlist and many segments into a directory.
+fn is_directory_style(path: &str) -> bool {
+    path.ends_with('/')
+        || path.ends_with('\\')
+        || std::path::Path::new(path).extension().is_none()
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct FfmpegArgs(pub Vec<String>);
 
 /// Build ffmpeg arguments for composing picture-in-picture using filter_complex.
 /// Assumes that slide video is generated elsewhere and provided as second input.
+///
+/// The `encoder` selects the video codec and, for VAAPI, switches to the
+/// hardware filter graph (`scale_vaapi`/`overlay_vaapi`) and device flags.
 pub fn build_ffmpeg_args(
     main_video_path: &str,
     overlay_video_path: &str,
@@ -41,6 +191,10 @@ pub fn build_ffmpeg_args(
     overlay_rel_w: f64,
     position: OverlayPosition,
     foreground: ForegroundKind,
+    encoder: Encoder,
+    output_format: OutputFormat,
+    fast_regions: &[(f64, f64, f64)],
+    has_audio: bool,
 ) -> FfmpegArgs {
     let (bg, fg) = match foreground {
         ForegroundKind::Video => ("1:v", "0:v"),
@@ -54,64 +208,265 @@ pub fn build_ffmpeg_args(
         OverlayPosition::BottomRight => ("W-w-16", "H-h-16"),
     };
 
-    let filter = format!(
-        "[{fg}]scale=1920*{overlay_rel_w}:-1[ov];[{bg}]scale=1920:1080:flags=bicubic[bg];[bg][ov]overlay={ox}:{oy}:eval=init,fps=30",
-    );
-
-    let args = vec![
+    let mut args: Vec<String> = vec![
         "-y".into(),
         "-hide_banner".into(),
         "-loglevel".into(),
         "warning".into(),
+    ];
+
+    let vaapi = encoder == Encoder::H264Vaapi;
+    if vaapi {
+        // Decode straight into VAAPI surfaces on the configured render node.
+        args.extend([
+            "-hwaccel".into(),
+            "vaapi".into(),
+            "-hwaccel_output_format".into(),
+            "vaapi".into(),
+            "-vaapi_device".into(),
+            "/dev/dri/renderD128".into(),
+        ]);
+    }
+
+    args.extend([
         "-i".into(),
         main_video_path.into(),
         "-i".into(),
         overlay_video_path.into(),
+    ]);
+
+    // Speed-ramp before the overlay stage (software path only). Both video
+    // layers are trimmed into the same pieces and time-scaled identically so
+    // the overlay stays aligned with the sped-up background; the prefix feeds
+    // pre-processed [bgff]/[fgff] (and [aff] when audio is present) streams.
+    let fast = !vaapi && !fast_regions.is_empty();
+    let (filter_prefix, bg_label, fg_label, audio_map) = if fast {
+        let prefix = build_fast_region_filter(bg, fg, fast_regions, has_audio);
+        let audio_map = if has_audio { "[aff]" } else { "0:a?" };
+        (
+            format!("{prefix};"),
+            "bgff".to_string(),
+            "fgff".to_string(),
+            audio_map.to_string(),
+        )
+    } else {
+        (String::new(), bg.to_string(), fg.to_string(), "0:a?".to_string())
+    };
+
+    let filter = if vaapi {
+        // Upload software inputs to NV12 VAAPI surfaces, then scale/overlay on
+        // the GPU. Only the first input (`0:v`) is hardware-decoded.
+        let w = (1920.0 * overlay_rel_w).round() as i64;
+        let upload = |label: &str| if label == "0:v" { "" } else { "format=nv12,hwupload," };
+        let (fg_up, bg_up) = (upload(fg), upload(bg));
+        format!(
+            "[{fg}]{fg_up}scale_vaapi=w={w}:h=-1[ov];[{bg}]{bg_up}scale_vaapi=w=1920:h=1080[bg];[bg][ov]overlay_vaapi={ox}:{oy}",
+        )
+    } else {
+        format!(
+            "{filter_prefix}[{fg_label}]scale=1920*{overlay_rel_w}:-1[ov];[{bg_label}]scale=1920:1080:flags=bicubic[bg];[bg][ov]overlay={ox}:{oy}:eval=init,fps=30",
+        )
+    };
+
+    args.extend([
         "-filter_complex".into(),
         filter,
         "-map".into(),
-        "0:a?".into(),
+        audio_map,
         "-c:v".into(),
-        "libx264".into(),
-        "-pix_fmt".into(),
-        "yuv420p".into(),
-        "-r".into(),
-        "30".into(),
-        "-s".into(),
-        "1920x1080".into(),
+        encoder.ffmpeg_codec().into(),
+    ]);
+
+    // VAAPI keeps frames as hardware surfaces, so skip the software pixel
+    // format and the `-s` software scaler the GPU filters already handle.
+    if !vaapi {
+        args.extend([
+            "-pix_fmt".into(),
+            "yuv420p".into(),
+            "-r".into(),
+            "30".into(),
+            "-s".into(),
+            "1920x1080".into(),
+        ]);
+    } else {
+        args.extend(["-r".into(), "30".into()]);
+    }
+
+    args.extend([
         "-c:a".into(),
         "aac".into(),
         "-b:a".into(),
         "192k".into(),
         "-shortest".into(),
-        "-movflags".into(),
-        "+faststart".into(),
-        output_path.into(),
-    ];
+    ]);
+
+    match output_format {
+        OutputFormat::Mp4 => {
+            args.extend([
+                "-movflags".into(),
+                "+faststart".into(),
+                output_path.into(),
+            ]);
+        }
+        OutputFormat::HlsFmp4 => {
+            let dir = output_path.trim_end_matches(['/', '\\']);
+            // Force keyframes on the segment grid so every segment is
+            // independently decodable (VOD seeking / adaptive delivery).
+            args.extend([
+                "-force_key_frames".into(),
+                format!("expr:gte(t,n_forced*{HLS_SEGMENT_SEC})"),
+                "-f".into(),
+                "hls".into(),
+                "-hls_segment_type".into(),
+                "fmp4".into(),
+                "-hls_time".into(),
+                HLS_SEGMENT_SEC.to_string(),
+                "-hls_playlist_type".into(),
+                "vod".into(),
+                // Per-segment .m4s files (matching -hls_segment_filename); no
+                // single_file, which would pack one blob and ignore the
+                // pattern. independent_segments keeps each one seekable.
+                "-hls_flags".into(),
+                "independent_segments".into(),
+                "-hls_fmp4_init_filename".into(),
+                "init.mp4".into(),
+                "-hls_segment_filename".into(),
+                format!("{dir}/seg_%05d.m4s"),
+                format!("{dir}/index.m3u8"),
+            ]);
+        }
+    }
 
     FfmpegArgs(args)
 }
 
-pub fn apply_quality(args: &mut FfmpegArgs, quality: QualityProfile) {
-    // tweak crf/preset for speed/quality
-    let (crf, preset) = match quality {
-        QualityProfile::Draft => (32, "veryfast"),
-        QualityProfile::Standard => (26, "medium"),
-        QualityProfile::High => (20, "slow"),
-    };
-    // insert before output path
+/// HLS target segment length in seconds; keyframes are forced on this grid so
+/// segments are independently decodable.
+const HLS_SEGMENT_SEC: u32 = 6;
+
+pub fn apply_quality(args: &mut FfmpegArgs, quality: QualityProfile, encoder: Encoder) {
     let v = &mut args.0;
-    // Find position of output path (last element)
+    // insert before output path (last element)
     let out = v.pop().unwrap_or_default();
-    v.extend([
-        "-crf".into(),
-        crf.to_string(),
-        "-preset".into(),
-        preset.into(),
-    ]);
+    match encoder {
+        Encoder::Libx264 => {
+            let (crf, preset) = match quality {
+                QualityProfile::Draft => (32, "veryfast"),
+                QualityProfile::Standard => (26, "medium"),
+                QualityProfile::High => (20, "slow"),
+            };
+            v.extend([
+                "-crf".into(),
+                crf.to_string(),
+                "-preset".into(),
+                preset.into(),
+            ]);
+        }
+        Encoder::Svtav1 => {
+            // SVT-AV1 preset is a 0..13 speed dial; lower is slower/better.
+            let (preset, crf) = match quality {
+                QualityProfile::Draft => (10, 45),
+                QualityProfile::Standard => (8, 35),
+                QualityProfile::High => (5, 28),
+            };
+            v.extend([
+                "-preset".into(),
+                preset.to_string(),
+                "-crf".into(),
+                crf.to_string(),
+            ]);
+        }
+        Encoder::H264Nvenc | Encoder::HevcNvenc => {
+            // NVENC presets p1 (fastest) .. p7 (slowest) paired with constant
+            // quality.
+            let (preset, cq) = match quality {
+                QualityProfile::Draft => ("p1", 32),
+                QualityProfile::Standard => ("p4", 26),
+                QualityProfile::High => ("p7", 20),
+            };
+            v.extend([
+                "-preset".into(),
+                preset.into(),
+                "-cq".into(),
+                cq.to_string(),
+            ]);
+        }
+        Encoder::H264Vaapi => {
+            // VAAPI uses -qp rather than -crf.
+            let qp = match quality {
+                QualityProfile::Draft => 32,
+                QualityProfile::Standard => 26,
+                QualityProfile::High => 20,
+            };
+            v.extend(["-qp".into(), qp.to_string()]);
+        }
+    }
     v.push(out);
 }
 
+/// A computed `xfade` chain for the slide track plus the label of its final
+/// output pad.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XfadeChain {
+    pub filter: String,
+    pub out_label: String,
+}
+
+/// Build the `xfade` filter graph that dissolves consecutive slide segments.
+///
+/// Inputs are assumed to be `durations.len()` still-image streams `[0]..[n-1]`,
+/// each looped to its duration. Returns `None` when there is nothing to fade
+/// (hard-cut transition, fewer than two slides, or a non-positive duration) so
+/// the caller can fall back to plain concat-copy.
+///
+/// The transition length is clamped so it never exceeds the shorter of any two
+/// adjacent slide durations, which also guards the first/last segment.
+pub fn build_xfade_chain(
+    durations: &[f64],
+    transition: crate::domain::TransitionKind,
+    transition_sec: f64,
+) -> Option<XfadeChain> {
+    let name = transition.xfade_name()?;
+    if durations.len() < 2 || transition_sec <= 0.0 {
+        return None;
+    }
+    let min_adjacent = durations
+        .windows(2)
+        .map(|w| w[0].min(w[1]))
+        .fold(f64::INFINITY, f64::min);
+    let d = transition_sec.min(min_adjacent);
+    if d <= 0.0 {
+        return None;
+    }
+
+    // xfade requires every input to share dimensions, pixel format and SAR, so
+    // normalize each still to 1080p/yuv420p/1:1 before it enters the chain.
+    let mut filter = String::new();
+    for i in 0..durations.len() {
+        filter.push_str(&format!(
+            "[{i}]scale=1920:1080,format=yuv420p,setsar=1[s{i}];",
+        ));
+    }
+
+    let mut prev = "s0".to_string();
+    let mut acc = 0.0; // running sum of durations consumed so far
+    for i in 1..durations.len() {
+        acc += durations[i - 1];
+        let offset = acc - d * i as f64;
+        let out = format!("v{i}");
+        filter.push_str(&format!(
+            "[{prev}][s{i}]xfade=transition={name}:duration={d}:offset={offset}[{out}];",
+        ));
+        prev = out;
+    }
+    // Drop the trailing ';'.
+    filter.pop();
+    Some(XfadeChain {
+        filter,
+        out_label: prev,
+    })
+}
+
 pub fn build_images_to_video_args(images_glob: &str, fps: u32, output_path: &str) -> FfmpegArgs {
     let args = vec![
         "-y".into(),
@@ -216,6 +571,8 @@ mod tests {
             overlay_relative_width: 0.25,
             foreground_kind: ForegroundKind::Slides,
             quality: QualityProfile::Standard,
+            encoder: Encoder::Libx264,
+            output_format: OutputFormat::Mp4,
             fps: None,
             output_width: None,
             output_height: None,
@@ -230,6 +587,8 @@ mod tests {
                     time_seconds: 1.0,
                 },
             ],
+            fast_regions: vec![],
+            parallel: false,
         };
         assert!(validate_request(&req).is_ok());
 
@@ -250,6 +609,10 @@ mod tests {
             0.2,
             OverlayPosition::TopRight,
             ForegroundKind::Slides,
+            Encoder::Libx264,
+            OutputFormat::Mp4,
+            &[],
+            false,
         );
         let joined = args.0.join(" ");
         assert!(joined.contains("-i main.mp4"));
@@ -281,13 +644,217 @@ mod tests {
             0.2,
             OverlayPosition::TopRight,
             ForegroundKind::Slides,
+            Encoder::Libx264,
+            OutputFormat::Mp4,
+            &[],
+            false,
         );
         // Remember last token (output)
         let last_before = args.0.last().cloned().unwrap();
-        apply_quality(&mut args, QualityProfile::Draft);
+        apply_quality(&mut args, QualityProfile::Draft, Encoder::Libx264);
         let joined = args.0.join(" ");
         assert_eq!(args.0.last().unwrap(), &last_before);
         assert!(joined.contains("-crf 32"));
         assert!(joined.contains("-preset veryfast"));
     }
+
+    #[test]
+    fn vaapi_encoder_uses_hardware_graph() {
+        let mut args = build_ffmpeg_args(
+            "main.mp4",
+            "overlay.mp4",
+            "out.mp4",
+            0.2,
+            OverlayPosition::TopRight,
+            ForegroundKind::Slides,
+            Encoder::H264Vaapi,
+            OutputFormat::Mp4,
+            &[],
+            false,
+        );
+        apply_quality(&mut args, QualityProfile::High, Encoder::H264Vaapi);
+        let joined = args.0.join(" ");
+        assert!(joined.contains("-hwaccel vaapi"));
+        assert!(joined.contains("-vaapi_device /dev/dri/renderD128"));
+        assert!(joined.contains("overlay_vaapi"));
+        assert!(joined.contains("-c:v h264_vaapi"));
+        assert!(joined.contains("-qp 20"));
+        assert!(!joined.contains("-crf"));
+    }
+
+    #[test]
+    fn xfade_chain_accumulates_offsets() {
+        use crate::domain::TransitionKind;
+        let chain = build_xfade_chain(&[5.0, 4.0, 6.0], TransitionKind::Fade, 1.0).unwrap();
+        // Each input is normalized before entering the chain.
+        assert!(chain.filter.contains("[0]scale=1920:1080,format=yuv420p,setsar=1[s0];"));
+        // First fade starts 1s before the end of slide 0.
+        assert!(chain.filter.contains("[s0][s1]xfade=transition=fade:duration=1:offset=4"));
+        // Second fade: (5+4) - 1*2 = 7.
+        assert!(chain.filter.contains("[v1][s2]xfade=transition=fade:duration=1:offset=7"));
+        assert_eq!(chain.out_label, "v2");
+    }
+
+    #[test]
+    fn xfade_chain_clamps_and_bails() {
+        use crate::domain::TransitionKind;
+        // None transition => no chain.
+        assert!(build_xfade_chain(&[5.0, 4.0], TransitionKind::None, 1.0).is_none());
+        // Single slide => nothing to fade.
+        assert!(build_xfade_chain(&[5.0], TransitionKind::Fade, 1.0).is_none());
+        // Requested 3s clamps to the 2s shorter neighbour.
+        let chain = build_xfade_chain(&[8.0, 2.0], TransitionKind::Fade, 3.0).unwrap();
+        assert!(chain.filter.contains("duration=2"));
+    }
+
+    #[test]
+    fn nvenc_encoder_maps_presets() {
+        let mut args = build_ffmpeg_args(
+            "main.mp4",
+            "overlay.mp4",
+            "out.mp4",
+            0.2,
+            OverlayPosition::TopRight,
+            ForegroundKind::Slides,
+            Encoder::H264Nvenc,
+            OutputFormat::Mp4,
+            &[],
+            false,
+        );
+        apply_quality(&mut args, QualityProfile::High, Encoder::H264Nvenc);
+        let joined = args.0.join(" ");
+        assert!(joined.contains("-c:v h264_nvenc"));
+        assert!(joined.contains("-preset p7"));
+        assert!(joined.contains("-cq 20"));
+    }
+
+    #[test]
+    fn hls_output_emits_muxer_flags() {
+        let args = build_ffmpeg_args(
+            "main.mp4",
+            "overlay.mp4",
+            "out/lecture",
+            0.2,
+            OverlayPosition::TopRight,
+            ForegroundKind::Slides,
+            Encoder::Libx264,
+            OutputFormat::HlsFmp4,
+            &[],
+            false,
+        );
+        let joined = args.0.join(" ");
+        assert!(joined.contains("-f hls"));
+        assert!(joined.contains("-hls_segment_type fmp4"));
+        assert!(joined.contains("-hls_flags independent_segments"));
+        assert!(!joined.contains("single_file"));
+        assert!(joined.contains("out/lecture/seg_%05d.m4s"));
+        assert!(joined.ends_with("out/lecture/index.m3u8"));
+        assert!(!joined.contains("+faststart"));
+    }
+
+    #[test]
+    fn hls_requires_directory_output() {
+        use crate::domain::{CompositionRequest, ForegroundKind, OverlayPosition};
+        let base = CompositionRequest {
+            pdf_path: "a.pdf".into(),
+            video_path: "b.mp4".into(),
+            output_path: "out.mp4".into(),
+            overlay_position: OverlayPosition::TopRight,
+            overlay_relative_width: 0.25,
+            foreground_kind: ForegroundKind::Slides,
+            quality: QualityProfile::Standard,
+            encoder: Encoder::Libx264,
+            output_format: OutputFormat::HlsFmp4,
+            fps: None,
+            output_width: None,
+            output_height: None,
+            expected_duration_sec: None,
+            timings: vec![SlideTiming {
+                slide_index: 0,
+                time_seconds: 0.1,
+            }],
+            fast_regions: vec![],
+            parallel: false,
+        };
+        assert!(matches!(
+            validate_request(&base),
+            Err(ValidationError::HlsRequiresDirectory(_))
+        ));
+
+        let mut ok = base;
+        ok.output_path = "out/lecture".into();
+        assert!(validate_request(&ok).is_ok());
+    }
+
+    #[test]
+    fn fast_regions_reject_overlap_and_order() {
+        assert!(validate_fast_regions(&[(0.0, 5.0, 2.0), (6.0, 9.0, 3.0)]).is_ok());
+        // Out of order.
+        assert!(validate_fast_regions(&[(6.0, 9.0, 2.0), (0.0, 5.0, 2.0)]).is_err());
+        // Overlapping.
+        assert!(validate_fast_regions(&[(0.0, 5.0, 2.0), (4.0, 9.0, 2.0)]).is_err());
+        // start >= end, and non-positive multiplier.
+        assert!(validate_fast_regions(&[(5.0, 5.0, 2.0)]).is_err());
+        assert!(validate_fast_regions(&[(0.0, 5.0, 0.0)]).is_err());
+    }
+
+    #[test]
+    fn atempo_chain_stays_in_range() {
+        assert_eq!(atempo_chain(1.5), vec![1.5]);
+        // 4x needs two 2x stages.
+        assert_eq!(atempo_chain(4.0), vec![2.0, 2.0]);
+        // 5x: 2 * 2 * 1.25.
+        assert_eq!(atempo_chain(5.0), vec![2.0, 2.0, 1.25]);
+    }
+
+    #[test]
+    fn fast_region_filter_splits_and_concats() {
+        let args = build_ffmpeg_args(
+            "main.mp4",
+            "overlay.mp4",
+            "out.mp4",
+            0.2,
+            OverlayPosition::TopRight,
+            ForegroundKind::Slides,
+            Encoder::Libx264,
+            OutputFormat::Mp4,
+            &[(10.0, 20.0, 2.0)],
+            true,
+        );
+        let joined = args.0.join(" ");
+        // Background split into normal/fast/normal pieces and concatenated.
+        assert!(joined.contains("trim=start=10:end=20,setpts=(PTS-STARTPTS)/2"));
+        assert!(joined.contains("concat=n=3:v=1:a=0[bgff]"));
+        // The overlay layer is time-scaled into the same pieces so it stays
+        // aligned with the sped-up background, and the overlay stage consumes
+        // both processed streams.
+        assert!(joined.contains("concat=n=3:v=1:a=0[fgff]"));
+        assert!(joined.contains("[fgff]scale=1920*0.2"));
+        assert!(joined.contains("[bgff]scale=1920:1080"));
+        // Audio time-scaled with atempo and remapped onto the processed stream.
+        assert!(joined.contains("atempo=2"));
+        assert!(joined.contains("-map [aff]"));
+    }
+
+    #[test]
+    fn fast_region_filter_omits_audio_when_absent() {
+        let args = build_ffmpeg_args(
+            "main.mp4",
+            "overlay.mp4",
+            "out.mp4",
+            0.2,
+            OverlayPosition::TopRight,
+            ForegroundKind::Slides,
+            Encoder::Libx264,
+            OutputFormat::Mp4,
+            &[(10.0, 20.0, 2.0)],
+            false,
+        );
+        let joined = args.0.join(" ");
+        // No audio track: the filtergraph must not reference the [0:a] pad, and
+        // the map falls back to the optional stream specifier.
+        assert!(!joined.contains("[0:a]"));
+        assert!(!joined.contains("[aff]"));
+        assert!(joined.contains("-map 0:a?"));
+    }
 }
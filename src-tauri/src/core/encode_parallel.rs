@@ -0,0 +1,287 @@
+//! Parallel chunked encoding of a composition.
+//!
+//! Rather than run a single long ffmpeg process, the timeline is split at the
+//! slide-switch boundaries carried in [`SlideTiming`], each segment is encoded
+//! independently in a bounded worker pool sized by
+//! [`std::thread::available_parallelism`], and the pieces are stitched back
+//! together with the concat demuxer (`-f concat -safe 0 -c copy`) — the same
+//! path used at the end of `build_slides_video_with_durations`.
+//!
+//! Because `-c copy` concat requires byte-compatible elementary streams, every
+//! chunk is forced to identical `yuv420p`, frame rate and GOP structure so the
+//! joins are seamless.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+use super::{apply_quality, build_ffmpeg_args, FfmpegArgs};
+use crate::domain::{CompositionRequest, Encoder, OutputFormat, SlideTiming};
+
+/// Keyframe interval forced on every chunk so concat joins land on a GOP
+/// boundary. Matches the 30 fps the filter graph pins, i.e. one keyframe per
+/// second.
+const SEGMENT_KEYINT: u32 = 30;
+
+/// A single independently-encodable time window of the composition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentPlan {
+    pub index: usize,
+    pub start_sec: f64,
+    pub duration: f64,
+    pub output: PathBuf,
+}
+
+/// Failure modes of a parallel encode batch.
+#[derive(thiserror::Error, Debug)]
+pub enum EncodeError {
+    #[error("segment {0} failed to encode")]
+    SegmentFailed(usize),
+    #[error("concat demuxer failed")]
+    ConcatFailed,
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Split the timeline into one segment per slide using the slide-switch times
+/// as natural cut points. The final segment runs to `total_duration_sec`.
+/// Zero- or negative-length windows (duplicate timings past the end) are
+/// skipped so the concat list only references segments that get encoded.
+pub fn plan_segments(
+    timings: &[SlideTiming],
+    total_duration_sec: f64,
+    seg_dir: &Path,
+) -> Vec<SegmentPlan> {
+    let mut plans = Vec::new();
+    let n = timings.len();
+    for i in 0..n {
+        let start = timings[i].time_seconds;
+        let end = if i + 1 < n {
+            timings[i + 1].time_seconds.min(total_duration_sec)
+        } else {
+            total_duration_sec
+        };
+        if end <= start {
+            continue;
+        }
+        plans.push(SegmentPlan {
+            index: i,
+            start_sec: start,
+            duration: end - start,
+            output: seg_dir.join(format!("seg_{i:05}.mp4")),
+        });
+    }
+    plans
+}
+
+/// Build the ffmpeg args for one chunk: the shared picture-in-picture filter
+/// graph and quality flags, plus an input seek (`-ss`/`-t`) and the
+/// concat-compatible stream parameters (`-pix_fmt yuv420p`, GOP, `keyint`).
+pub fn build_segment_args(req: &CompositionRequest, plan: &SegmentPlan) -> FfmpegArgs {
+    let mut args = build_ffmpeg_args(
+        &req.video_path,
+        &req.pdf_path,
+        &plan.output.to_string_lossy(),
+        req.overlay_relative_width,
+        req.overlay_position,
+        req.foreground_kind,
+        req.encoder,
+        // Chunks are always progressive MP4; HLS packaging happens on the
+        // stitched result, not per-segment.
+        OutputFormat::Mp4,
+        // Speed ramps rewrite the whole timeline and are incompatible with the
+        // per-chunk input seek; they only apply on the single-pass path.
+        &[],
+        // No speed ramp here, so the audio filtergraph is never built and the
+        // flag is irrelevant; the optional `0:a?` map handles silent inputs.
+        false,
+    );
+    apply_quality(&mut args, req.quality, req.encoder);
+    inject_input_seek(&mut args, plan.start_sec, plan.duration);
+    force_concat_params(&mut args, req.encoder);
+    args
+}
+
+/// Seek each input to `start` (input-side `-ss`, fast) and bound the chunk to
+/// `duration` (output-side `-t`).
+fn inject_input_seek(args: &mut FfmpegArgs, start: f64, duration: f64) {
+    let mut out = Vec::with_capacity(args.0.len() + 6);
+    let mut i = 0;
+    while i < args.0.len() {
+        if args.0[i] == "-i" {
+            out.push("-ss".into());
+            out.push(format!("{start}"));
+        }
+        out.push(args.0[i].clone());
+        i += 1;
+    }
+    let output = out.pop().unwrap_or_default();
+    out.push("-t".into());
+    out.push(format!("{duration}"));
+    out.push(output);
+    args.0 = out;
+}
+
+/// Force identical codec parameters across every chunk so `-c copy` concat
+/// produces a seamless join. `-pix_fmt yuv420p` is already set by the base
+/// builder on the software path (the VAAPI path omits it); here we pin the GOP
+/// and, for libx264, the x264 keyframe cadence.
+fn force_concat_params(args: &mut FfmpegArgs, encoder: Encoder) {
+    let output = args.0.pop().unwrap_or_default();
+    args.0.extend(["-g".into(), SEGMENT_KEYINT.to_string()]);
+    if encoder == Encoder::Libx264 {
+        args.0.extend([
+            "-x264-params".into(),
+            format!("keyint={SEGMENT_KEYINT}:min-keyint={SEGMENT_KEYINT}:scenecut=0"),
+        ]);
+    }
+    args.0.push(output);
+}
+
+/// Encode every segment in a bounded worker pool and stitch the results with
+/// the concat demuxer. The first failing worker aborts the batch and the
+/// failing segment index is surfaced; the concat list is written in segment
+/// order regardless of the order segments finished.
+pub fn encode_parallel(
+    ffmpeg: &str,
+    req: &CompositionRequest,
+    total_duration_sec: f64,
+    output_path: &str,
+    seg_dir: &Path,
+) -> Result<(), EncodeError> {
+    std::fs::create_dir_all(seg_dir)?;
+    let plans = plan_segments(&req.timings, total_duration_sec, seg_dir);
+    let jobs: Vec<FfmpegArgs> = plans.iter().map(|p| build_segment_args(req, p)).collect();
+
+    let workers = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let next = Mutex::new(0usize);
+    let failed: Mutex<Option<usize>> = Mutex::new(None);
+
+    std::thread::scope(|s| {
+        for _ in 0..workers.min(jobs.len().max(1)) {
+            s.spawn(|| loop {
+                if failed.lock().unwrap().is_some() {
+                    break;
+                }
+                let i = {
+                    let mut g = next.lock().unwrap();
+                    let i = *g;
+                    *g += 1;
+                    i
+                };
+                if i >= jobs.len() {
+                    break;
+                }
+                let ran = Command::new(ffmpeg).args(&jobs[i].0).status();
+                let ok = matches!(ran, Ok(st) if st.success());
+                if !ok {
+                    let mut f = failed.lock().unwrap();
+                    if f.is_none() {
+                        *f = Some(plans[i].index);
+                    }
+                    break;
+                }
+            });
+        }
+    });
+
+    if let Some(idx) = *failed.lock().unwrap() {
+        return Err(EncodeError::SegmentFailed(idx));
+    }
+
+    let mut list = String::new();
+    for p in &plans {
+        list.push_str(&format!("file '{}'\n", p.output.to_string_lossy()));
+    }
+    let list_path = seg_dir.join("concat.txt");
+    std::fs::write(&list_path, list)?;
+
+    let status = Command::new(ffmpeg)
+        .args([
+            "-y",
+            "-f",
+            "concat",
+            "-safe",
+            "0",
+            "-i",
+            &list_path.to_string_lossy(),
+            "-c",
+            "copy",
+            output_path,
+        ])
+        .status()?;
+    if !status.success() {
+        return Err(EncodeError::ConcatFailed);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{
+        Encoder, ForegroundKind, OutputFormat, OverlayPosition, QualityProfile, SlideTiming,
+    };
+
+    fn timing(index: u32, t: f64) -> SlideTiming {
+        SlideTiming {
+            slide_index: index,
+            time_seconds: t,
+        }
+    }
+
+    #[test]
+    fn plan_segments_uses_timings_as_cut_points() {
+        let timings = vec![timing(0, 0.0), timing(1, 10.0), timing(2, 25.0)];
+        let plans = plan_segments(&timings, 40.0, Path::new("/tmp/segs"));
+        assert_eq!(plans.len(), 3);
+        assert_eq!(plans[0].start_sec, 0.0);
+        assert_eq!(plans[0].duration, 10.0);
+        assert_eq!(plans[1].duration, 15.0);
+        assert_eq!(plans[2].start_sec, 25.0);
+        assert_eq!(plans[2].duration, 15.0);
+        assert!(plans[2].output.ends_with("seg_00002.mp4"));
+    }
+
+    #[test]
+    fn plan_segments_skips_windows_past_total() {
+        let timings = vec![timing(0, 0.0), timing(1, 30.0)];
+        let plans = plan_segments(&timings, 20.0, Path::new("/tmp/segs"));
+        // Second slide starts after the video ends, so it yields no segment.
+        assert_eq!(plans.len(), 1);
+        assert_eq!(plans[0].duration, 20.0);
+    }
+
+    #[test]
+    fn segment_args_carry_seek_and_concat_params() {
+        let timings = vec![timing(0, 0.0), timing(1, 10.0)];
+        let plans = plan_segments(&timings, 20.0, Path::new("/tmp/segs"));
+        let req = CompositionRequest {
+            pdf_path: "slides.mp4".into(),
+            video_path: "cam.mp4".into(),
+            output_path: "out.mp4".into(),
+            overlay_position: OverlayPosition::TopRight,
+            overlay_relative_width: 0.2,
+            foreground_kind: ForegroundKind::Slides,
+            quality: QualityProfile::Standard,
+            encoder: Encoder::Libx264,
+            output_format: OutputFormat::Mp4,
+            fps: None,
+            output_width: None,
+            output_height: None,
+            expected_duration_sec: None,
+            timings,
+            fast_regions: vec![],
+            parallel: false,
+        };
+        let args = build_segment_args(&req, &plans[1]);
+        let joined = args.0.join(" ");
+        assert!(joined.contains("-ss 10"));
+        assert!(joined.contains("-t 10"));
+        assert!(joined.contains("-pix_fmt yuv420p"));
+        assert!(joined.contains("keyint=30"));
+        assert!(joined.ends_with("seg_00001.mp4"));
+    }
+}
@@ -0,0 +1,220 @@
+//! Typed media inspection backed by `ffprobe`.
+//!
+//! Replaces the fragile "grep ffmpeg stderr for `Duration:`" probe with a
+//! structured call to
+//! `ffprobe -v quiet -print_format json -show_format -show_streams`, whose
+//! JSON is deserialized into [`MediaInfo`]. Callers that only need the running
+//! time keep using [`MediaInfo::duration`]; richer consumers can inspect the
+//! per-stream [`StreamInfo`] to validate inputs before composition.
+
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+/// Kind of an elementary stream, coarsened from ffprobe's `codec_type`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum StreamKind {
+    Video,
+    Audio,
+    Other,
+}
+
+/// One elementary stream of a media file.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StreamInfo {
+    pub kind: StreamKind,
+    pub codec_name: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub pix_fmt: Option<String>,
+    /// `avg_frame_rate` parsed from the `"num/den"` string into frames/sec.
+    pub avg_frame_rate: Option<f64>,
+    pub bit_rate: Option<u64>,
+    pub channels: Option<u32>,
+}
+
+/// Container-level metadata plus every elementary stream.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MediaInfo {
+    pub duration_sec: Option<f64>,
+    pub format_name: Option<String>,
+    pub streams: Vec<StreamInfo>,
+}
+
+impl MediaInfo {
+    /// Backward-compatible accessor for the running time in seconds.
+    pub fn duration(&self) -> Option<f64> {
+        self.duration_sec
+    }
+
+    /// The first video stream, if any — useful for auto-filling output
+    /// width/height/fps from a presenter recording.
+    pub fn first_video(&self) -> Option<&StreamInfo> {
+        self.streams.iter().find(|s| s.kind == StreamKind::Video)
+    }
+
+    /// Whether the file carries at least one video stream.
+    pub fn has_video(&self) -> bool {
+        self.first_video().is_some()
+    }
+
+    /// Whether the file carries at least one audio stream.
+    pub fn has_audio(&self) -> bool {
+        self.streams.iter().any(|s| s.kind == StreamKind::Audio)
+    }
+}
+
+/// Errors surfaced while probing.
+#[derive(thiserror::Error, Debug)]
+pub enum ProbeError {
+    #[error("failed to run ffprobe: {0}")]
+    Spawn(#[source] std::io::Error),
+    #[error("ffprobe exited unsuccessfully")]
+    Failed,
+    #[error("failed to parse ffprobe output: {0}")]
+    Parse(#[source] serde_json::Error),
+}
+
+/// Shell out to `ffprobe` and deserialize its JSON report.
+pub fn probe_media(ffprobe: &str, path: &str) -> Result<MediaInfo, ProbeError> {
+    let output = Command::new(ffprobe)
+        .args([
+            "-v",
+            "quiet",
+            "-print_format",
+            "json",
+            "-show_format",
+            "-show_streams",
+            path,
+        ])
+        .output()
+        .map_err(ProbeError::Spawn)?;
+    if !output.status.success() {
+        return Err(ProbeError::Failed);
+    }
+    parse_ffprobe_json(&output.stdout)
+}
+
+/// Parse the raw `ffprobe -print_format json` output into a [`MediaInfo`].
+pub fn parse_ffprobe_json(bytes: &[u8]) -> Result<MediaInfo, ProbeError> {
+    let raw: RawReport = serde_json::from_slice(bytes).map_err(ProbeError::Parse)?;
+    Ok(raw.into())
+}
+
+/// Parse ffprobe's `"num/den"` rate string into frames per second. Returns
+/// `None` for the `"0/0"` placeholder ffprobe emits when a rate is unknown.
+pub fn parse_frame_rate(rate: &str) -> Option<f64> {
+    let (num, den) = rate.split_once('/')?;
+    let num: f64 = num.trim().parse().ok()?;
+    let den: f64 = den.trim().parse().ok()?;
+    if den == 0.0 {
+        return None;
+    }
+    Some(num / den)
+}
+
+// ffprobe's JSON layout keeps most numeric fields as strings; these raw types
+// mirror that and are mapped into the clean public structs above.
+#[derive(Deserialize)]
+struct RawReport {
+    #[serde(default)]
+    format: RawFormat,
+    #[serde(default)]
+    streams: Vec<RawStream>,
+}
+
+#[derive(Deserialize, Default)]
+struct RawFormat {
+    duration: Option<String>,
+    format_name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawStream {
+    codec_type: Option<String>,
+    codec_name: Option<String>,
+    width: Option<u32>,
+    height: Option<u32>,
+    pix_fmt: Option<String>,
+    avg_frame_rate: Option<String>,
+    bit_rate: Option<String>,
+    channels: Option<u32>,
+}
+
+impl From<RawReport> for MediaInfo {
+    fn from(raw: RawReport) -> Self {
+        MediaInfo {
+            duration_sec: raw.format.duration.as_deref().and_then(|d| d.trim().parse().ok()),
+            format_name: raw.format.format_name,
+            streams: raw.streams.into_iter().map(StreamInfo::from).collect(),
+        }
+    }
+}
+
+impl From<RawStream> for StreamInfo {
+    fn from(raw: RawStream) -> Self {
+        let kind = match raw.codec_type.as_deref() {
+            Some("video") => StreamKind::Video,
+            Some("audio") => StreamKind::Audio,
+            _ => StreamKind::Other,
+        };
+        StreamInfo {
+            kind,
+            codec_name: raw.codec_name,
+            width: raw.width,
+            height: raw.height,
+            pix_fmt: raw.pix_fmt,
+            avg_frame_rate: raw.avg_frame_rate.as_deref().and_then(parse_frame_rate),
+            bit_rate: raw.bit_rate.as_deref().and_then(|b| b.trim().parse().ok()),
+            channels: raw.channels,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_rate_parses_ntsc() {
+        let fps = parse_frame_rate("30000/1001").unwrap();
+        assert!((fps - 29.97).abs() < 0.01);
+        assert_eq!(parse_frame_rate("0/0"), None);
+        assert_eq!(parse_frame_rate("25/1"), Some(25.0));
+    }
+
+    #[test]
+    fn deserializes_streams_and_format() {
+        let json = br#"{
+            "format": {"duration": "123.45", "format_name": "mov,mp4,m4a"},
+            "streams": [
+                {"codec_type": "video", "codec_name": "h264", "width": 1920,
+                 "height": 1080, "pix_fmt": "yuv420p", "avg_frame_rate": "30/1",
+                 "bit_rate": "4500000"},
+                {"codec_type": "audio", "codec_name": "aac",
+                 "avg_frame_rate": "0/0", "channels": 2}
+            ]
+        }"#;
+        let info = parse_ffprobe_json(json).unwrap();
+        assert_eq!(info.duration(), Some(123.45));
+        assert_eq!(info.format_name.as_deref(), Some("mov,mp4,m4a"));
+        assert!(info.has_video());
+        let v = info.first_video().unwrap();
+        assert_eq!(v.width, Some(1920));
+        assert_eq!(v.avg_frame_rate, Some(30.0));
+        assert_eq!(v.bit_rate, Some(4_500_000));
+        assert_eq!(info.streams[1].kind, StreamKind::Audio);
+        assert_eq!(info.streams[1].channels, Some(2));
+        assert!(info.has_audio());
+    }
+
+    #[test]
+    fn detects_file_without_video() {
+        let json = br#"{"format": {}, "streams": [{"codec_type": "audio"}]}"#;
+        let info = parse_ffprobe_json(json).unwrap();
+        assert!(!info.has_video());
+        assert!(info.has_audio());
+        assert_eq!(info.duration(), None);
+    }
+}
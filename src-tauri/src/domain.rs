@@ -24,6 +24,58 @@ pub enum QualityProfile {
     High,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Encoder {
+    #[default]
+    Libx264,
+    H264Vaapi,
+    H264Nvenc,
+    HevcNvenc,
+    Svtav1,
+}
+
+impl Encoder {
+    /// The ffmpeg `-c:v` codec name for this encoder.
+    pub fn ffmpeg_codec(self) -> &'static str {
+        match self {
+            Encoder::Libx264 => "libx264",
+            Encoder::H264Vaapi => "h264_vaapi",
+            Encoder::H264Nvenc => "h264_nvenc",
+            Encoder::HevcNvenc => "hevc_nvenc",
+            Encoder::Svtav1 => "libsvtav1",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    #[default]
+    Mp4,
+    HlsFmp4,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum TransitionKind {
+    #[default]
+    None,
+    Fade,
+    Slide,
+}
+
+impl TransitionKind {
+    /// The ffmpeg `xfade=transition=` name, or `None` for a hard cut.
+    pub fn xfade_name(self) -> Option<&'static str> {
+        match self {
+            TransitionKind::None => None,
+            TransitionKind::Fade => Some("fade"),
+            TransitionKind::Slide => Some("slideleft"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SlideTiming {
     pub slide_index: u32,
@@ -42,6 +94,12 @@ pub struct CompositionRequest {
     pub foreground_kind: ForegroundKind,
     /// Encoding quality/speed
     pub quality: QualityProfile,
+    /// Video encoder; defaults to software libx264.
+    #[serde(default)]
+    pub encoder: Encoder,
+    /// Output container; defaults to a progressive MP4.
+    #[serde(default)]
+    pub output_format: OutputFormat,
     /// output frames per second
     pub fps: Option<u32>,
     /// output width x height; if None, inherit from main video
@@ -51,6 +109,15 @@ pub struct CompositionRequest {
     pub expected_duration_sec: Option<f64>,
     /// slide switch times; must be sorted by time_seconds ascending and cover all slides
     pub timings: Vec<SlideTiming>,
+    /// accelerated regions as (start_sec, end_sec, speed_multiplier); must be
+    /// sorted by start and non-overlapping
+    #[serde(default)]
+    pub fast_regions: Vec<(f64, f64, f64)>,
+    /// Encode the timeline in parallel chunks (one per slide) and concat them,
+    /// instead of one blocking ffmpeg pass. Faster on long lectures; limited to
+    /// progressive MP4 without speed ramps.
+    #[serde(default)]
+    pub parallel: bool,
 }
 
 #[derive(thiserror::Error, Debug, PartialEq)]
@@ -63,4 +130,8 @@ pub enum ValidationError {
     NonIncreasingTimings,
     #[error("slide indices must start at 0 and be contiguous")]
     InvalidSlideIndices,
+    #[error("HLS output requires a directory-style output path, got {0}")]
+    HlsRequiresDirectory(String),
+    #[error("fast regions must have start < end, positive speed, and be sorted and non-overlapping")]
+    InvalidFastRegions,
 }
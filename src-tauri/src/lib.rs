@@ -18,6 +18,7 @@ pub fn run() {
             tauri_commands::create_temp_dir,
             tauri_commands::build_slides_video_with_durations,
             tauri_commands::probe_video_duration,
+            tauri_commands::probe_media_info,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -1,10 +1,17 @@
-use crate::core::{apply_quality, build_ffmpeg_args, build_images_to_video_args, validate_request};
-use crate::domain::CompositionRequest;
+use crate::core::probe::{probe_media, MediaInfo, ProbeError};
+use crate::core::encode_parallel::encode_parallel;
+use crate::core::{
+    apply_quality, build_ffmpeg_args, build_images_to_video_args, build_xfade_chain,
+    validate_request,
+};
+use crate::domain::{CompositionRequest, Encoder, OutputFormat, TransitionKind};
 use base64::Engine as _;
 use serde::Deserialize;
 use std::fs;
+use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
-use std::process::Command;
+use std::process::{Command, Stdio};
+use tauri::Emitter;
 
 #[derive(serde::Serialize, serde::Deserialize, Default)]
 struct FfmpegConfig {
@@ -53,6 +60,21 @@ fn ffmpeg_path() -> String {
         "ffmpeg".into()
     }
 }
+/// Locate `ffprobe` next to the configured `ffmpeg` binary, falling back to a
+/// bare `ffprobe` on `PATH`.
+fn ffprobe_path() -> String {
+    let mut ff = ffmpeg_path();
+    // Replace the last occurrence so a directory named "ffmpeg" is kept.
+    if let Some(pos) = ff.rfind("ffmpeg") {
+        ff.replace_range(pos..pos + "ffmpeg".len(), "ffprobe");
+        ff
+    } else if cfg!(target_os = "windows") {
+        "ffprobe.exe".into()
+    } else {
+        "ffprobe".into()
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct PdfCountArgs {
     #[serde(alias = "pdfPath")]
@@ -67,19 +89,100 @@ pub fn get_pdf_page_count(args: PdfCountArgs) -> Result<u32, String> {
     }
 }
 
+/// Downgrade to `libx264` if the requested hardware encoder isn't listed by
+/// `ffmpeg -encoders`. Software libx264 is assumed always present.
+fn resolve_encoder(ffmpeg: &str, requested: Encoder) -> Encoder {
+    if requested == Encoder::Libx264 {
+        return Encoder::Libx264;
+    }
+    let listed = Command::new(ffmpeg)
+        .args(["-hide_banner", "-encoders"])
+        .output()
+        .ok()
+        .map(|o| {
+            String::from_utf8_lossy(&o.stdout).contains(requested.ffmpeg_codec())
+        })
+        .unwrap_or(false);
+    if listed {
+        requested
+    } else {
+        Encoder::Libx264
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ComposeArgs {
     pub request: CompositionRequest,
 }
 
+/// Progress snapshot forwarded to the frontend on the `compose-progress`
+/// event. `percent` is `None` until enough data is available (and stays `None`
+/// when no duration estimate was supplied, except on the final event, which
+/// always reports 100).
+#[derive(Clone, serde::Serialize)]
+pub struct ComposeProgress {
+    pub percent: Option<f64>,
+    pub fps: f64,
+    pub speed: f64,
+}
+
 #[tauri::command]
-pub async fn compose_video(args: ComposeArgs) -> Result<(), String> {
+pub async fn compose_video(app: tauri::AppHandle, args: ComposeArgs) -> Result<(), String> {
     let req = args.request;
     validate_request(&req).map_err(|e| e.to_string())?;
 
     let overlay_rel_w = req.overlay_relative_width;
     let position = req.overlay_position;
     let foreground = req.foreground_kind;
+    // Fall back to software libx264 when the requested hardware encoder is not
+    // advertised by this ffmpeg build.
+    let encoder = resolve_encoder(&ffmpeg_path(), req.encoder);
+
+    // HLS writes the playlist and segments into a directory; create it up front
+    // so the muxer does not fail on a missing path.
+    if req.output_format == OutputFormat::HlsFmp4 {
+        fs::create_dir_all(&req.output_path).map_err(|e| e.to_string())?;
+    }
+
+    // Parallel chunked encoding: split at slide boundaries, encode each segment
+    // in a worker pool, and concat the pieces. Only valid for progressive MP4
+    // without speed ramps (per-chunk input seeking is incompatible with the
+    // whole-timeline ramp graph); everything else falls through to the single
+    // blocking pass below.
+    if req.parallel && req.output_format == OutputFormat::Mp4 && req.fast_regions.is_empty() {
+        let mut req = req.clone();
+        req.encoder = encoder;
+        let total = match req.expected_duration_sec {
+            Some(d) if d > 0.0 => d,
+            _ => probe_media(&ffprobe_path(), &req.video_path)
+                .ok()
+                .and_then(|info| info.duration())
+                .ok_or_else(|| "could not determine input duration".to_string())?,
+        };
+        let seg_dir = PathBuf::from(&req.output_path)
+            .parent()
+            .map(|p| p.join("segments"))
+            .ok_or_else(|| "invalid output path".to_string())?;
+        encode_parallel(&ffmpeg_path(), &req, total, &req.output_path, &seg_dir)
+            .map_err(|e| e.to_string())?;
+        let _ = app.emit(
+            "compose-progress",
+            ComposeProgress {
+                percent: Some(100.0),
+                fps: 0.0,
+                speed: 0.0,
+            },
+        );
+        return Ok(());
+    }
+
+    // Speed ramps route audio through the filtergraph, which needs a real
+    // `[0:a]` pad; probe the source so a silent recording falls back to the
+    // optional map instead of aborting ffmpeg on an unresolved pad.
+    let has_audio = !req.fast_regions.is_empty()
+        && probe_media(&ffprobe_path(), &req.video_path)
+            .map(|info| info.has_audio())
+            .unwrap_or(false);
 
     let mut args = build_ffmpeg_args(
         &req.video_path,
@@ -88,13 +191,71 @@ pub async fn compose_video(args: ComposeArgs) -> Result<(), String> {
         overlay_rel_w,
         position,
         foreground,
+        encoder,
+        req.output_format,
+        &req.fast_regions,
+        has_audio,
     );
-    apply_quality(&mut args, req.quality);
+    apply_quality(&mut args, req.quality, encoder);
 
     // stream progress from ffmpeg through stderr lines with -progress pipe:2
-    let mut cmd = Command::new(ffmpeg_path());
-    cmd.args(["-progress", "pipe:2"]).args(&args.0);
-    let status = cmd.status().map_err(|e| e.to_string())?;
+    let mut child = Command::new(ffmpeg_path())
+        .args(["-progress", "pipe:2"])
+        .args(&args.0)
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    let expected = req.expected_duration_sec;
+    if let Some(stderr) = child.stderr.take() {
+        let reader = BufReader::new(stderr);
+        let mut fps = 0.0_f64;
+        let mut speed = 0.0_f64;
+        let mut percent: Option<f64> = None;
+        for line in reader.lines().map_while(Result::ok) {
+            // ffmpeg emits `key=value` progress blocks terminated by a
+            // `progress=continue|end` marker; ignore anything that isn't a
+            // recognised key so partial/garbled lines are harmless.
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            match key.trim() {
+                "fps" => fps = value.trim().parse().unwrap_or(fps),
+                "out_time_ms" => {
+                    // Despite the name, ffmpeg reports `out_time_ms` in
+                    // microseconds, so convert with 1e6 to get elapsed seconds.
+                    if let (Ok(us), Some(total)) = (value.trim().parse::<f64>(), expected) {
+                        if total > 0.0 {
+                            percent = Some(((us / 1_000_000.0) / total * 100.0).clamp(0.0, 100.0));
+                        }
+                    }
+                }
+                "speed" => {
+                    speed = value.trim().trim_end_matches('x').trim().parse().unwrap_or(speed);
+                }
+                "progress" => {
+                    let done = value.trim() == "end";
+                    if done {
+                        percent = Some(100.0);
+                    }
+                    let _ = app.emit(
+                        "compose-progress",
+                        ComposeProgress {
+                            percent,
+                            fps,
+                            speed,
+                        },
+                    );
+                    if done {
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let status = child.wait().map_err(|e| e.to_string())?;
     if !status.success() {
         return Err(format!("ffmpeg failed with status: {status}"));
     }
@@ -157,12 +318,68 @@ pub struct BuildSlidesVideoDurationsArgs {
     pub frames_dir: String,
     pub durations: Vec<f64>,
     pub output_path: String,
+    /// Transition between slides; defaults to a hard cut.
+    #[serde(default)]
+    pub transition: TransitionKind,
+    /// Transition length in seconds (clamped to the shorter adjacent slide).
+    #[serde(default)]
+    pub transition_sec: f64,
+}
+
+/// Resolve and existence-check the PNG path for slide `index`.
+fn slide_image_path(frames_dir: &str, index: usize) -> Result<PathBuf, String> {
+    let path = std::path::Path::new(frames_dir).join(format!("{:05}.png", index as u32));
+    if !path.exists() {
+        return Err(format!("missing slide image: {}", path.to_string_lossy()));
+    }
+    Ok(path)
 }
 
 #[tauri::command]
 pub async fn build_slides_video_with_durations(
     args: BuildSlidesVideoDurationsArgs,
 ) -> Result<(), String> {
+    // With a transition requested, build the track in one pass using xfade
+    // (which needs decoded frames, so `-c copy` concat is out).
+    if let Some(chain) =
+        build_xfade_chain(&args.durations, args.transition, args.transition_sec)
+    {
+        let mut ff: Vec<String> = vec!["-y".into()];
+        for (i, dur) in args.durations.iter().enumerate() {
+            let slide_path = slide_image_path(&args.frames_dir, i)?;
+            ff.extend([
+                "-loop".into(),
+                "1".into(),
+                "-t".into(),
+                format!("{dur}"),
+                "-i".into(),
+                slide_path.to_string_lossy().to_string(),
+            ]);
+        }
+        ff.extend([
+            "-filter_complex".into(),
+            chain.filter,
+            "-map".into(),
+            format!("[{}]", chain.out_label),
+            // Dimensions/format are normalized per-input inside the xfade graph.
+            "-r".into(),
+            "30".into(),
+            "-c:v".into(),
+            "libx264".into(),
+            "-pix_fmt".into(),
+            "yuv420p".into(),
+            args.output_path.clone(),
+        ]);
+        let status = Command::new(ffmpeg_path())
+            .args(&ff)
+            .status()
+            .map_err(|e| e.to_string())?;
+        if !status.success() {
+            return Err("ffmpeg xfade render failed".into());
+        }
+        return Ok(());
+    }
+
     let tmp = std::path::Path::new(&args.output_path)
         .parent()
         .map(|p| p.join("segments"))
@@ -171,14 +388,7 @@ pub async fn build_slides_video_with_durations(
 
     let mut list = String::new();
     for (i, dur) in args.durations.iter().enumerate() {
-        let slide_path =
-            std::path::Path::new(&args.frames_dir).join(format!("{:05}.png", i as u32));
-        if !slide_path.exists() {
-            return Err(format!(
-                "missing slide image: {}",
-                slide_path.to_string_lossy()
-            ));
-        }
+        let slide_path = slide_image_path(&args.frames_dir, i)?;
         let seg_path = tmp.join(format!("seg_{i:05}.mp4"));
         let ff = vec![
             "-y".into(),
@@ -250,6 +460,26 @@ pub struct ProbeVideoArgs {
     pub video_path: String,
 }
 
+#[tauri::command]
+pub fn probe_media_info(args: ProbeVideoArgs) -> Result<MediaInfo, String> {
+    match probe_media(&ffprobe_path(), &args.video_path) {
+        Ok(info) => Ok(info),
+        // Degrade gracefully when ffprobe is missing: fall back to the legacy
+        // ffmpeg stderr parse and report just the duration.
+        Err(ProbeError::Spawn(_)) => {
+            let duration = probe_video_duration(ProbeVideoArgs {
+                video_path: args.video_path,
+            })?;
+            Ok(MediaInfo {
+                duration_sec: Some(duration),
+                format_name: None,
+                streams: Vec::new(),
+            })
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
 #[tauri::command]
 pub fn probe_video_duration(args: ProbeVideoArgs) -> Result<f64, String> {
     let output = Command::new(ffmpeg_path())